@@ -3,12 +3,15 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use notify::{RecommendedWatcher, Watcher};
+use notify::{EventKind, RecommendedWatcher, Watcher};
+use notify::event::ModifyKind;
 use notify_debouncer_full::{DebounceEventResult, new_debouncer};
 use tokio::sync::Mutex;
 
 use crate::config::{SherryConfig, SherryConfigJSON, SherryConfigWatcherJSON};
+use crate::control::ControlState;
 use crate::event::event_processing::{BasedDebounceEvent, EventProcessingDebounce};
+use crate::index::FileIndex;
 use crate::logs::initialize_logs;
 use crate::server::socket::SocketClient;
 
@@ -25,6 +28,9 @@ fn get_source_by_path<'a>(config: &'a SherryConfigJSON, path: &PathBuf) -> Optio
 pub struct App {
     pub config: Arc<Mutex<SherryConfig>>,
     pub socket: Arc<Mutex<SocketClient>>,
+    pub index: Arc<FileIndex>,
+    pub control: ControlState,
+    pub config_dir: PathBuf,
 }
 
 impl App {
@@ -37,12 +43,24 @@ impl App {
         let config = SherryConfig::new(config_dir).await.expect("Unable to initialize configuration, maybe access is denied");
         log::info!("Initialized configuration");
 
-        let socket = SocketClient::new(&config).await;
+        let socket = match SocketClient::new(&config).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::error!("Socket initialization failed: {}", e);
+                return Err(());
+            }
+        };
         log::info!("Connected to socket");
 
+        let index = FileIndex::new(config_dir).expect("Unable to initialize file index");
+        log::info!("Initialized file index");
+
         Ok(App {
             config: Arc::new(Mutex::new(config)),
             socket: Arc::new(Mutex::new(socket)),
+            index: Arc::new(index),
+            control: ControlState::default(),
+            config_dir: config_dir.clone(),
         })
     }
 
@@ -86,6 +104,31 @@ impl App {
                             continue;
                         }
 
+                        if app.control.is_paused(&source_id).await {
+                            continue;
+                        }
+
+                        let path = source_path.unwrap().clone();
+                        match result.event.kind {
+                            EventKind::Remove(_) => {
+                                if let Err(e) = app.index.remove(&source_id, &local_path, &path).await {
+                                    log::error!("Failed to remove index entry for {:?}: {}", path, e);
+                                }
+                            }
+                            EventKind::Modify(ModifyKind::Name(_)) if result.paths.len() > 1 => {
+                                if let Err(e) = app.index.rename(&source_id, &local_path, &result.paths[0], &result.paths[1]).await {
+                                    log::error!("Failed to rename index entry from {:?} to {:?}: {}", result.paths[0], result.paths[1], e);
+                                }
+                            }
+                            _ => {
+                                match app.index.should_forward(&source_id, &local_path, &path).await {
+                                    Ok(false) => continue,
+                                    Ok(true) => (),
+                                    Err(e) => log::error!("Index lookup failed, forwarding anyway: {}", e),
+                                }
+                            }
+                        }
+
                         let debounce = event_processing_debounce_map
                             .entry(source_id.clone())
                             .or_insert(EventProcessingDebounce::new(&rt, &app, &source_id));
@@ -103,10 +146,32 @@ impl App {
 
                     if should_revalidate {
                         main_watcher_config.lock().await.revalidate().await;
+                        let config = main_watcher_config.lock().await.get_main().await;
+                        for watcher in &config.watchers {
+                            if !watcher.complete {
+                                continue;
+                            }
+                            let local_path = PathBuf::from(&watcher.local_path);
+                            if !local_path.exists() {
+                                continue;
+                            }
+                            if let Err(e) = app.index.rebuild(&watcher.source, &local_path).await {
+                                log::error!("Failed to rebuild index for {}: {}", watcher.source, e);
+                            }
+                        }
                     }
                 }
             });
         }).unwrap();
+
+        let control_app = self.clone();
+        let config_dir = self.config_dir.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::control::listen(&config_dir, control_app).await {
+                log::error!("Control socket failed, continuing without it: {}", e);
+            }
+        });
+
         SherryConfig::listen(&self.config, &self.socket, &Arc::new(Mutex::new(debouncer))).await;
     }
 }
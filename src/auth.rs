@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use qrencode::render::unicode;
+use qrencode::QrCode;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::helpers::str_err_prefix;
+use crate::files::write_json_file;
+
+const CREDENTIALS_FILE: &str = "credentials.json";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PairingRequest {
+    id: String,
+    code: String,
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+enum PairingStatus {
+    Pending,
+    Approved { credentials: Credentials },
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub account_id: String,
+    pub token: String,
+}
+
+async fn request_pairing(client: &reqwest::Client, server_url: &str) -> Result<PairingRequest, String> {
+    client.post(format!("{}/pairing", server_url))
+        .send().await.map_err(str_err_prefix("Error Pairing Request"))?
+        .json::<PairingRequest>().await.map_err(str_err_prefix("Error Pairing Decode"))
+}
+
+async fn poll_pairing(client: &reqwest::Client, server_url: &str, request: &PairingRequest) -> Result<Credentials, String> {
+    let deadline = tokio::time::Instant::now() + POLL_TIMEOUT;
+
+    loop {
+        if tokio::time::Instant::now() >= deadline {
+            return Err("Pairing request expired before it was approved".to_string());
+        }
+
+        let status = client.get(format!("{}/pairing/{}", server_url, request.id))
+            .send().await.map_err(str_err_prefix("Error Pairing Poll"))?
+            .json::<PairingStatus>().await.map_err(str_err_prefix("Error Pairing Decode"))?;
+
+        match status {
+            PairingStatus::Approved { credentials } => return Ok(credentials),
+            PairingStatus::Expired => return Err("Pairing request expired".to_string()),
+            PairingStatus::Pending => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+}
+
+fn print_pairing_code(request: &PairingRequest) {
+    match QrCode::new(&request.url) {
+        Ok(code) => {
+            let image = code.render::<unicode::Dense1x2>()
+                .quiet_zone(false)
+                .build();
+            println!("{}", image);
+        }
+        Err(e) => log::warn!("Unable to render pairing URL as a QR code, falling back to the raw code: {}", e),
+    }
+
+    println!("Scan the QR code above, or open the following URL to authorize this device:");
+    println!("  {}", request.url);
+    println!("If you can't scan or open it, enter this code instead: {}", request.code);
+}
+
+#[cfg(unix)]
+async fn restrict_to_owner(path: &PathBuf) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await
+        .map_err(str_err_prefix("Error Credentials Permissions"))
+}
+
+#[cfg(not(unix))]
+async fn restrict_to_owner(_path: &PathBuf) -> Result<(), String> {
+    Ok(())
+}
+
+pub async fn pair(config_dir: &PathBuf, server_url: &str) -> Result<(), String> {
+    let client = reqwest::Client::new();
+
+    let request = request_pairing(&client, server_url).await?;
+    print_pairing_code(&request);
+
+    log::info!("Waiting for pairing request {} to be approved", request.id);
+    let credentials = poll_pairing(&client, server_url, &request).await?;
+
+    let credentials_path = config_dir.join(CREDENTIALS_FILE);
+    write_json_file(&credentials_path, &credentials).await?;
+    restrict_to_owner(&credentials_path).await?;
+    log::info!("Paired successfully, credentials saved");
+    println!("Device paired successfully.");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_url_over_qr_capacity_does_not_panic() {
+        let request = PairingRequest {
+            id: "abc".to_string(),
+            code: "123-456".to_string(),
+            url: format!("https://example.com/pair?token={}", "a".repeat(5000)),
+        };
+
+        print_pairing_code(&request);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn credentials_file_is_restricted_to_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CREDENTIALS_FILE);
+        fs::write(&path, "{}").await.unwrap();
+
+        restrict_to_owner(&path).await.unwrap();
+
+        let mode = fs::metadata(&path).await.unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+use crate::app::App;
+use crate::helpers::str_err_prefix;
+
+const SOCKET_NAME: &str = "control.sock";
+const MAX_FRAME_LEN: u32 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControlCommand {
+    Status,
+    ListWatchers,
+    Pause { source_id: String },
+    Resume { source_id: String },
+    Revalidate,
+    ReloadConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControlResponse {
+    Status { watchers: usize, paused: Vec<String> },
+    Watchers(Vec<String>),
+    Ok,
+    Error(String),
+}
+
+#[derive(Clone, Default)]
+pub struct ControlState {
+    pub paused: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ControlState {
+    pub async fn is_paused(&self, source_id: &str) -> bool {
+        self.paused.lock().await.contains(source_id)
+    }
+}
+
+fn socket_path(config_dir: &PathBuf) -> PathBuf {
+    config_dir.join(SOCKET_NAME)
+}
+
+async fn handle_command(command: ControlCommand, app: &App) -> ControlResponse {
+    match command {
+        ControlCommand::Status => {
+            let config = app.config.lock().await.get_main().await;
+            let paused = app.control.paused.lock().await.iter().cloned().collect();
+            ControlResponse::Status { watchers: config.watchers.len(), paused }
+        }
+        ControlCommand::ListWatchers => {
+            let config = app.config.lock().await.get_main().await;
+            ControlResponse::Watchers(config.watchers.iter().map(|w| w.source.clone()).collect())
+        }
+        ControlCommand::Pause { source_id } => {
+            app.control.paused.lock().await.insert(source_id);
+            ControlResponse::Ok
+        }
+        ControlCommand::Resume { source_id } => {
+            app.control.paused.lock().await.remove(&source_id);
+            ControlResponse::Ok
+        }
+        ControlCommand::Revalidate => {
+            app.config.lock().await.revalidate().await;
+            let config = app.config.lock().await.get_main().await;
+            for watcher in &config.watchers {
+                if !watcher.complete {
+                    continue;
+                }
+                let local_path = PathBuf::from(&watcher.local_path);
+                if !local_path.exists() {
+                    continue;
+                }
+                if let Err(e) = app.index.rebuild(&watcher.source, &local_path).await {
+                    return ControlResponse::Error(e);
+                }
+            }
+            ControlResponse::Ok
+        }
+        ControlCommand::ReloadConfig => {
+            match app.config.lock().await.reload().await {
+                Ok(()) => ControlResponse::Ok,
+                Err(e) => ControlResponse::Error(e),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod transport {
+    use tokio::net::{UnixListener, UnixStream};
+
+    use super::*;
+
+    pub async fn listen(config_dir: &PathBuf, app: App) -> Result<(), String> {
+        let path = socket_path(config_dir);
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).map_err(str_err_prefix("Error Control Bind"))?;
+        log::info!("Control socket listening at: {:?}", path);
+
+        loop {
+            let (stream, _) = listener.accept().await.map_err(str_err_prefix("Error Control Accept"))?;
+            let app = app.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, app).await {
+                    log::error!("Control connection failed: {}", e);
+                }
+            });
+        }
+    }
+
+    pub async fn send(config_dir: &PathBuf, command: &ControlCommand) -> Result<ControlResponse, String> {
+        let stream = UnixStream::connect(socket_path(config_dir)).await.map_err(str_err_prefix("Error Control Connect"))?;
+        request(stream, command).await
+    }
+
+    async fn handle_connection(mut stream: UnixStream, app: App) -> Result<(), String> {
+        let command = read_command(&mut stream).await?;
+        let response = handle_command(command, &app).await;
+        write_response(&mut stream, &response).await
+    }
+
+    async fn request(mut stream: UnixStream, command: &ControlCommand) -> Result<ControlResponse, String> {
+        write_command(&mut stream, command).await?;
+        read_response(&mut stream).await
+    }
+}
+
+#[cfg(windows)]
+mod transport {
+    use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions};
+
+    use super::*;
+
+    fn pipe_name(config_dir: &PathBuf) -> String {
+        format!(r"\\.\pipe\{}", socket_path(config_dir).to_string_lossy().replace(['\\', '/'], "-"))
+    }
+
+    pub async fn listen(config_dir: &PathBuf, app: App) -> Result<(), String> {
+        let name = pipe_name(config_dir);
+        log::info!("Control pipe listening at: {}", name);
+
+        loop {
+            let mut server = ServerOptions::new().create(&name).map_err(str_err_prefix("Error Control Pipe Create"))?;
+            server.connect().await.map_err(str_err_prefix("Error Control Pipe Connect"))?;
+            let app = app.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(server, app).await {
+                    log::error!("Control connection failed: {}", e);
+                }
+            });
+        }
+    }
+
+    pub async fn send(config_dir: &PathBuf, command: &ControlCommand) -> Result<ControlResponse, String> {
+        let mut client = ClientOptions::new().open(pipe_name(config_dir)).map_err(str_err_prefix("Error Control Pipe Open"))?;
+        write_command(&mut client, command).await?;
+        read_response(&mut client).await
+    }
+
+    async fn handle_connection(mut pipe: NamedPipeServer, app: App) -> Result<(), String> {
+        let command = read_command(&mut pipe).await?;
+        let response = handle_command(command, &app).await;
+        write_response(&mut pipe, &response).await
+    }
+}
+
+async fn read_frame(stream: &mut (impl AsyncReadExt + Unpin)) -> Result<Vec<u8>, String> {
+    let len = stream.read_u32().await.map_err(str_err_prefix("Error Control Read Length"))?;
+    if len > MAX_FRAME_LEN {
+        return Err(format!("Control frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_LEN));
+    }
+    let mut buf = vec![0u8; len as usize];
+    stream.read_exact(&mut buf).await.map_err(str_err_prefix("Error Control Read Body"))?;
+    Ok(buf)
+}
+
+async fn read_command(stream: &mut (impl AsyncReadExt + Unpin)) -> Result<ControlCommand, String> {
+    serde_json::from_slice(&read_frame(stream).await?).map_err(str_err_prefix("Error Control Decode"))
+}
+
+async fn read_response(stream: &mut (impl AsyncReadExt + Unpin)) -> Result<ControlResponse, String> {
+    serde_json::from_slice(&read_frame(stream).await?).map_err(str_err_prefix("Error Control Decode"))
+}
+
+async fn write_command(stream: &mut (impl AsyncWriteExt + Unpin), command: &ControlCommand) -> Result<(), String> {
+    let body = serde_json::to_vec(command).map_err(str_err_prefix("Error Control Encode"))?;
+    stream.write_u32(body.len() as u32).await.map_err(str_err_prefix("Error Control Write Length"))?;
+    stream.write_all(&body).await.map_err(str_err_prefix("Error Control Write Body"))
+}
+
+async fn write_response(stream: &mut (impl AsyncWriteExt + Unpin), response: &ControlResponse) -> Result<(), String> {
+    let body = serde_json::to_vec(response).map_err(str_err_prefix("Error Control Encode"))?;
+    stream.write_u32(body.len() as u32).await.map_err(str_err_prefix("Error Control Write Length"))?;
+    stream.write_all(&body).await.map_err(str_err_prefix("Error Control Write Body"))
+}
+
+pub async fn listen(config_dir: &PathBuf, app: App) -> Result<(), String> {
+    transport::listen(config_dir, app).await
+}
+
+pub async fn send_command(config_dir: &PathBuf, command: &ControlCommand) -> Result<ControlResponse, String> {
+    transport::send(config_dir, command).await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn command_frame_round_trips() {
+        let (mut a, mut b) = duplex(1024);
+        let command = ControlCommand::Pause { source_id: "src-a".to_string() };
+
+        write_command(&mut a, &command).await.unwrap();
+        let decoded = read_command(&mut b).await.unwrap();
+
+        match decoded {
+            ControlCommand::Pause { source_id } => assert_eq!(source_id, "src-a"),
+            _ => panic!("unexpected command: {:?}", decoded),
+        }
+    }
+
+    #[tokio::test]
+    async fn response_frame_round_trips() {
+        let (mut a, mut b) = duplex(1024);
+        let response = ControlResponse::Status { watchers: 3, paused: vec!["src-a".to_string()] };
+
+        write_response(&mut a, &response).await.unwrap();
+        let decoded = read_response(&mut b).await.unwrap();
+
+        match decoded {
+            ControlResponse::Status { watchers, paused } => {
+                assert_eq!(watchers, 3);
+                assert_eq!(paused, vec!["src-a".to_string()]);
+            }
+            _ => panic!("unexpected response: {:?}", decoded),
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_frame_is_rejected_before_allocating_it() {
+        let (mut a, mut b) = duplex(16);
+        a.write_u32(MAX_FRAME_LEN + 1).await.unwrap();
+
+        let result = read_command(&mut b).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn pause_then_resume_clears_paused_state() {
+        let state = ControlState::default();
+
+        assert!(!state.is_paused("src-a").await);
+        state.paused.lock().await.insert("src-a".to_string());
+        assert!(state.is_paused("src-a").await);
+        state.paused.lock().await.remove("src-a");
+        assert!(!state.is_paused("src-a").await);
+    }
+}
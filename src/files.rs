@@ -8,6 +8,7 @@ use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio_util::bytes::Bytes;
 
+use crate::hash::Hasher;
 use crate::helpers::str_err_prefix;
 
 pub async fn write_json_file<T, P: AsRef<Path>>(path: P, value: &T) -> Result<(), String>
@@ -100,6 +101,65 @@ pub async fn write_files_from_stream(paths: &Vec<PathBuf>, mut stream: impl Stre
     Ok(())
 }
 
+const PART_SUFFIX: &str = "part";
+const RESUME_READ_CHUNK: usize = 64 * 1024;
+
+fn part_path(path: &PathBuf) -> PathBuf {
+    let mut part = path.clone().into_os_string();
+    part.push(".");
+    part.push(PART_SUFFIX);
+    PathBuf::from(part)
+}
+
+pub async fn resumable_offset(path: &PathBuf) -> u64 {
+    fs::metadata(part_path(path)).await.map(|m| m.len()).unwrap_or(0)
+}
+
+pub async fn write_file_from_stream_resumable(
+    path: &PathBuf,
+    expected_hash: &[u8; 32],
+    offset: u64,
+    mut stream: impl Stream<Item=Result<Bytes, reqwest::Error>> + Unpin,
+) -> Result<(), String> {
+    let part = part_path(path);
+    let mut hasher = Hasher::new();
+
+    let mut file = if offset > 0 {
+        let actual_len = fs::metadata(&part).await.map_err(str_err_prefix("Error Part File Stat"))?.len();
+        if actual_len != offset {
+            return Err(format!("Resume offset {} does not match .part file length {} for {:?}", offset, actual_len, path));
+        }
+
+        let mut existing = fs::File::open(&part).await.map_err(str_err_prefix("Error Part File Open"))?;
+        let mut buf = [0u8; RESUME_READ_CHUNK];
+        loop {
+            let read = existing.read(&mut buf).await.map_err(str_err_prefix("Error Part File Read"))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+
+        fs::OpenOptions::new().append(true).open(&part).await.map_err(str_err_prefix("Error Part File Open"))?
+    } else {
+        create_file(&part).await?
+    };
+
+    while let Some(chunk_result) = stream.next().await {
+        let chunk = chunk_result.map_err(str_err_prefix("Invalid chunk"))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(str_err_prefix("Error Write"))?;
+    }
+
+    let hash = hasher.finalize();
+    if hash != *expected_hash {
+        delete_path(&part).await?;
+        return Err(format!("Hash mismatch for {:?}: downloaded content does not match expected digest", path));
+    }
+
+    rename_path(&part, path).await
+}
+
 pub async fn delete_path(path: &PathBuf) -> Result<(), String> {
     if path.is_dir() {
         fs::remove_dir_all(&path).await.map_err(str_err_prefix(format!("Error Dir Remove at {}", &path.to_str().unwrap())))?;
@@ -113,3 +173,50 @@ pub async fn rename_path(old: &PathBuf, new: &PathBuf) -> Result<(), String> {
     fs::rename(old, new).await.map_err(str_err_prefix("Error File/Folder Rename"))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn hash_bytes(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Hasher::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    #[tokio::test]
+    async fn resumable_offset_reports_existing_part_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.bin");
+        assert_eq!(resumable_offset(&path).await, 0);
+
+        fs::write(part_path(&path), b"hello").await.unwrap();
+        assert_eq!(resumable_offset(&path).await, 5);
+    }
+
+    #[tokio::test]
+    async fn resumable_write_rejects_stale_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.bin");
+        fs::write(part_path(&path), b"hello").await.unwrap();
+
+        let chunks: Vec<Result<Bytes, reqwest::Error>> = vec![Ok(Bytes::from_static(b" world"))];
+        let result = write_file_from_stream_resumable(&path, &hash_bytes(b"hello world"), 3, stream::iter(chunks)).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn resumable_write_resumes_and_verifies_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.bin");
+        fs::write(part_path(&path), b"hello").await.unwrap();
+
+        let chunks: Vec<Result<Bytes, reqwest::Error>> = vec![Ok(Bytes::from_static(b" world"))];
+        write_file_from_stream_resumable(&path, &hash_bytes(b"hello world"), 5, stream::iter(chunks)).await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+    }
+}
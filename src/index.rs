@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::hash::hash_file;
+use crate::helpers::{normalize_path, str_err_prefix};
+
+const INDEX_DIR: &str = "index";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub hash: [u8; 32],
+    pub mtime: i64,
+    pub size: u64,
+}
+
+#[derive(Clone)]
+pub struct FileIndex {
+    db: sled::Db,
+    trees: Arc<Mutex<HashMap<String, sled::Tree>>>,
+}
+
+impl FileIndex {
+    pub fn new(config_dir: &PathBuf) -> Result<FileIndex, String> {
+        let db = sled::open(config_dir.join(INDEX_DIR)).map_err(str_err_prefix("Error Index Open"))?;
+        Ok(FileIndex { db, trees: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    async fn tree(&self, source_id: &str) -> Result<sled::Tree, String> {
+        let mut trees = self.trees.lock().await;
+        if let Some(tree) = trees.get(source_id) {
+            return Ok(tree.clone());
+        }
+        let tree = self.db.open_tree(source_id).map_err(str_err_prefix("Error Index Tree Open"))?;
+        trees.insert(source_id.to_string(), tree.clone());
+        Ok(tree)
+    }
+
+    fn key(local_path: &PathBuf, path: &PathBuf) -> Vec<u8> {
+        let relative = path.strip_prefix(local_path).unwrap_or(path);
+        normalize_path(&relative.to_path_buf()).to_string_lossy().into_owned().into_bytes()
+    }
+
+    pub async fn get(&self, source_id: &str, local_path: &PathBuf, path: &PathBuf) -> Result<Option<FileRecord>, String> {
+        let tree = self.tree(source_id).await?;
+        match tree.get(Self::key(local_path, path)).map_err(str_err_prefix("Error Index Read"))? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes).map_err(str_err_prefix("Error Index Decode"))?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn upsert(&self, source_id: &str, local_path: &PathBuf, path: &PathBuf, record: &FileRecord) -> Result<(), String> {
+        let tree = self.tree(source_id).await?;
+        let bytes = bincode::serialize(record).map_err(str_err_prefix("Error Index Encode"))?;
+        tree.insert(Self::key(local_path, path), bytes).map_err(str_err_prefix("Error Index Write"))?;
+        Ok(())
+    }
+
+    pub async fn remove(&self, source_id: &str, local_path: &PathBuf, path: &PathBuf) -> Result<(), String> {
+        let tree = self.tree(source_id).await?;
+        tree.remove(Self::key(local_path, path)).map_err(str_err_prefix("Error Index Remove"))?;
+        Ok(())
+    }
+
+    pub async fn rename(&self, source_id: &str, local_path: &PathBuf, old: &PathBuf, new: &PathBuf) -> Result<(), String> {
+        if let Some(record) = self.get(source_id, local_path, old).await? {
+            self.upsert(source_id, local_path, new, &record).await?;
+        }
+        self.remove(source_id, local_path, old).await
+    }
+
+    pub async fn should_forward(&self, source_id: &str, local_path: &PathBuf, path: &PathBuf) -> Result<bool, String> {
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(true),
+        };
+        let mtime = metadata.modified().map_err(str_err_prefix("Error Read Mtime"))?
+            .duration_since(UNIX_EPOCH).map_err(str_err_prefix("Error Read Mtime"))?
+            .as_millis() as i64;
+        let size = metadata.len();
+        let existing = self.get(source_id, local_path, path).await?;
+
+        if let Some(existing) = &existing {
+            if existing.mtime == mtime && existing.size == size {
+                return Ok(false);
+            }
+        }
+
+        let hash = hash_file(path).await?;
+        let changed = existing.map_or(true, |existing| existing.hash != hash);
+        self.upsert(source_id, local_path, path, &FileRecord { hash, mtime, size }).await?;
+        Ok(changed)
+    }
+
+    pub async fn rebuild(&self, source_id: &str, local_path: &PathBuf) -> Result<(), String> {
+        let tree = self.tree(source_id).await?;
+        tree.clear().map_err(str_err_prefix("Error Index Clear"))?;
+
+        let mut dirs = vec![local_path.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = tokio::fs::read_dir(&dir).await.map_err(str_err_prefix("Error Dir Read"))?;
+            while let Some(entry) = entries.next_entry().await.map_err(str_err_prefix("Error Dir Entry"))? {
+                let path = entry.path();
+                let metadata = entry.metadata().await.map_err(str_err_prefix("Error Read Metadata"))?;
+                if metadata.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let mtime = metadata.modified().map_err(str_err_prefix("Error Read Mtime"))?
+                    .duration_since(UNIX_EPOCH).map_err(str_err_prefix("Error Read Mtime"))?
+                    .as_millis() as i64;
+                let hash = hash_file(&path).await?;
+                self.upsert(source_id, local_path, &path, &FileRecord { hash, mtime, size: metadata.len() }).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn upsert_then_get_roundtrips() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = FileIndex::new(&dir.path().to_path_buf()).unwrap();
+        let local_path = PathBuf::from("/watched");
+        let path = PathBuf::from("/watched/file.txt");
+        let record = FileRecord { hash: [1; 32], mtime: 1_700_000_000_123, size: 42 };
+
+        index.upsert("source-a", &local_path, &path, &record).await.unwrap();
+        let fetched = index.get("source-a", &local_path, &path).await.unwrap().unwrap();
+
+        assert_eq!(fetched.mtime, record.mtime);
+        assert_eq!(fetched.size, record.size);
+    }
+
+    #[tokio::test]
+    async fn rename_moves_the_record_and_clears_the_old_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = FileIndex::new(&dir.path().to_path_buf()).unwrap();
+        let local_path = PathBuf::from("/watched");
+        let old_path = PathBuf::from("/watched/old.txt");
+        let new_path = PathBuf::from("/watched/new.txt");
+        let record = FileRecord { hash: [2; 32], mtime: 1_700_000_000_456, size: 7 };
+
+        index.upsert("source-a", &local_path, &old_path, &record).await.unwrap();
+        index.rename("source-a", &local_path, &old_path, &new_path).await.unwrap();
+
+        assert!(index.get("source-a", &local_path, &old_path).await.unwrap().is_none());
+        assert!(index.get("source-a", &local_path, &new_path).await.unwrap().is_some());
+    }
+
+    #[test]
+    fn key_is_relative_to_the_watcher_root() {
+        let local_path = PathBuf::from("/watched");
+        let path = PathBuf::from("/watched/nested/file.txt");
+
+        assert_eq!(FileIndex::key(&local_path, &path), b"nested/file.txt".to_vec());
+    }
+
+    #[test]
+    fn mtime_keeps_sub_second_precision() {
+        // Two writes 500ms apart land on the same whole second; storing millis
+        // (not `as_secs()`) is what lets `should_forward` tell them apart.
+        let a = FileRecord { hash: [0; 32], mtime: 1_700_000_000_100, size: 10 };
+        let b = FileRecord { hash: [0; 32], mtime: 1_700_000_000_600, size: 10 };
+
+        assert_eq!(a.mtime / 1000, b.mtime / 1000);
+        assert_ne!(a.mtime, b.mtime);
+    }
+}
@@ -1,12 +1,13 @@
 use std::env;
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use home::home_dir;
 use path_clean::PathClean;
 
 use crate::app::App;
 use crate::constants::CONFIG_DIR;
+use crate::control::ControlCommand;
 
 mod events;
 mod config;
@@ -18,11 +19,37 @@ mod helpers;
 mod constants;
 mod server;
 mod files;
+mod index;
+mod control;
 
 #[derive(Parser)]
 struct Args {
     #[arg(short, long, default_missing_value = None)]
     config: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the daemon's current status
+    Status,
+    /// List the watchers the daemon knows about
+    ListWatchers,
+    /// Stop syncing a watcher without restarting the daemon
+    Pause { source_id: String },
+    /// Resume a previously paused watcher
+    Resume { source_id: String },
+    /// Re-read the local tree for every watcher
+    Revalidate,
+    /// Re-read the configuration file from disk
+    ReloadConfig,
+    /// Authorize this daemon instance against an account by scanning a QR code
+    Pair {
+        #[arg(long)]
+        server: String,
+    },
 }
 
 fn resolve_config_dir(config: Option<String>) -> PathBuf {
@@ -45,6 +72,25 @@ async fn main() -> Result<(), String> {
 
     let config_dir = resolve_config_dir(args.config);
 
+    if let Some(Command::Pair { server }) = &args.command {
+        return auth::pair(&config_dir, server).await;
+    }
+
+    if let Some(command) = args.command {
+        let command = match command {
+            Command::Status => ControlCommand::Status,
+            Command::ListWatchers => ControlCommand::ListWatchers,
+            Command::Pause { source_id } => ControlCommand::Pause { source_id },
+            Command::Resume { source_id } => ControlCommand::Resume { source_id },
+            Command::Revalidate => ControlCommand::Revalidate,
+            Command::ReloadConfig => ControlCommand::ReloadConfig,
+            Command::Pair { .. } => unreachable!("handled above"),
+        };
+        let response = control::send_command(&config_dir, &command).await?;
+        println!("{:?}", response);
+        return Ok(());
+    }
+
     let app = App::new(&config_dir).await;
     if app.is_err() { return Err("Demon start failed".to_string()); }
     let mut app = app.unwrap();
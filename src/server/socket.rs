@@ -0,0 +1,97 @@
+use futures::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::config::SherryConfig;
+use crate::helpers::str_err_prefix;
+
+/// Bump when the wire format changes in a way old daemons can't decode.
+const PROTO_VERSION: u8 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope<T> {
+    proto: u8,
+    kind: String,
+    payload: T,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Hello {
+    proto: u8,
+}
+
+pub struct SocketClient {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl SocketClient {
+    pub async fn new(config: &SherryConfig) -> Result<SocketClient, String> {
+        let url = config.get_socket_url();
+        let (stream, _) = connect_async(&url).await.map_err(str_err_prefix("Error Socket Connect"))?;
+        let mut client = SocketClient { stream };
+
+        client.handshake().await?;
+        Ok(client)
+    }
+
+    async fn handshake(&mut self) -> Result<(), String> {
+        let hello = Hello { proto: PROTO_VERSION };
+        let body = rmp_serde::to_vec_named(&hello).map_err(str_err_prefix("Error MessagePack Encode"))?;
+        self.stream.send(Message::Binary(body)).await.map_err(str_err_prefix("Error Socket Send"))?;
+
+        let ack: Hello = match self.stream.next().await {
+            Some(Ok(Message::Binary(body))) => rmp_serde::from_slice(&body).map_err(str_err_prefix("Error MessagePack Decode"))?,
+            Some(Ok(_)) => return Err("Unexpected handshake frame".to_string()),
+            Some(Err(e)) => return Err(str_err_prefix("Error Socket Handshake")(e)),
+            None => return Err("Socket closed during handshake".to_string()),
+        };
+
+        check_protocol_version(&ack)
+    }
+
+    pub async fn send<T: Serialize>(&mut self, kind: &str, payload: T) -> Result<(), String> {
+        let envelope = Envelope { proto: PROTO_VERSION, kind: kind.to_string(), payload };
+        let body = rmp_serde::to_vec_named(&envelope).map_err(str_err_prefix("Error MessagePack Encode"))?;
+        self.stream.send(Message::Binary(body)).await.map_err(str_err_prefix("Error Socket Send"))
+    }
+
+    pub async fn recv<T: DeserializeOwned>(&mut self) -> Result<Option<T>, String> {
+        match self.stream.next().await {
+            Some(Ok(Message::Binary(body))) => {
+                let envelope: Envelope<T> = rmp_serde::from_slice(&body).map_err(str_err_prefix("Error MessagePack Decode"))?;
+                Ok(Some(envelope.payload))
+            }
+            Some(Ok(_)) => Ok(None),
+            Some(Err(e)) => Err(str_err_prefix("Error Socket Receive")(e)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn check_protocol_version(ack: &Hello) -> Result<(), String> {
+    if ack.proto != PROTO_VERSION {
+        return Err(str_err_prefix("Error Socket Handshake")(format!(
+            "protocol mismatch: server speaks {}, client speaks {}", ack.proto, PROTO_VERSION
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_mismatched_protocol_version() {
+        let err = check_protocol_version(&Hello { proto: PROTO_VERSION + 1 }).unwrap_err();
+        assert!(err.contains("protocol mismatch"));
+    }
+
+    #[test]
+    fn accepts_matching_protocol_version() {
+        assert!(check_protocol_version(&Hello { proto: PROTO_VERSION }).is_ok());
+    }
+}